@@ -19,6 +19,7 @@ library IEEE;
 use IEEE.STD_LOGIC_1164.ALL;
 use IEEE.NUMERIC_STD.ALL;
 use ieee.math_real.all;
+{package_use}
 library UNISIM;
 use UNISIM.VComponents.all;
 --=============================================================================
@@ -50,21 +51,20 @@ uut: {component_name}
 	port map(		
 {port_connections});
 --=============================================================================
---clk_100MHz generation 
+--Clock generation (self-terminating: stops when running goes false)
 --=============================================================================
-clkgen_proc: process
-begin
 {clk_gen}
-end process clkgen_proc;
 --=============================================================================
 --Stimulus Process
 --=============================================================================
 stim_proc: process
-begin				
+{stim_proc_vars}
+begin
 {stim_proc}
     wait;
 end process stim_proc;
-end testbench;"#;
+end testbench;
+{configurations}"#;
 
 use clap::{Arg, Command};
 
@@ -86,27 +86,65 @@ pub struct VhdlGeneric {
     pub default_value: Option<String>,
 }
 
+#[derive(Debug, Clone)]
+pub struct VhdlRecordField {
+    pub name: String,
+    pub field_type: String,
+    pub range: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct VhdlRecordType {
+    pub name: String,
+    pub fields: Vec<VhdlRecordField>,
+}
+
 #[derive(Debug)]
 pub struct VhdlEntity {
     pub name: String,
     pub generics: Vec<VhdlGeneric>,
     pub ports: Vec<VhdlPort>,
+    pub record_types: Vec<VhdlRecordType>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct TestVector {
+    /// Settle delay applied after driving `inputs` and before checking
+    /// `expected_outputs` — doubles as the per-vector `wait`/`delay_ns`.
     pub time_ns: u32,
     pub inputs: HashMap<String, String>,
     pub expected_outputs: Option<HashMap<String, String>>,
     pub description: Option<String>,
 }
 
+/// A single named clock generator: period plus an optional phase offset
+/// applied before the clock starts toggling.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ClockConfig {
+    pub period_ns: u32,
+    pub phase_offset_ns: Option<u32>,
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct TestbenchConfig {
     pub clock_period_ns: Option<u32>,
     pub reset_duration_ns: Option<u32>,
     pub test_vectors: Option<Vec<TestVector>>,
     pub generics: Option<HashMap<String, String>>,
+    /// Port-map wiring style for record-typed ports: "flat" (default) wires
+    /// each port as a single aggregate signal, "record" expands the port map
+    /// into one `dut.field => tb.field` connection per record field.
+    pub reg_style: Option<String>,
+    /// Named architectures of the DUT (e.g. "rtl", "behavioral"). When set,
+    /// one top-level `configuration` is emitted per entry, each binding the
+    /// `uut` instance to that architecture, so a single testbench can verify
+    /// every implementation variant.
+    pub architectures: Option<Vec<String>>,
+    /// Additional named clocks (keyed by signal name), each with its own
+    /// period and optional phase offset. The default `tb_clk` generator is
+    /// always emitted; entries here add further self-terminating generators,
+    /// e.g. for DUTs with independent core/bus clock domains.
+    pub clocks: Option<HashMap<String, ClockConfig>>,
 }
 
 pub struct VhdlParser;
@@ -116,9 +154,21 @@ impl VhdlParser {
         let path = path.as_ref();
         let content = fs::read_to_string(path)
             .map_err(|e| format!("Failed to read VHDL file '{}': {}", path.display(), e))?;
-        Self::parse_content(&content).map_err(|e| -> Box<dyn std::error::Error> {
+        let mut entity = Self::parse_content(&content).map_err(|e| -> Box<dyn std::error::Error> {
             format!("Failed to parse VHDL file '{}': {}", path.display(), e).into()
-        })
+        })?;
+
+        // Record interfaces are usually declared in an associated package
+        // (e.g. `<entity>_pkg.vhd`) rather than the entity file itself.
+        let pkg_path = path.with_file_name(format!("{}_pkg.vhd", entity.name));
+        if let Ok(pkg_content) = fs::read_to_string(&pkg_path) {
+            let cleaned = Self::clean_content(&pkg_content);
+            entity
+                .record_types
+                .extend(Self::extract_record_types(&cleaned));
+        }
+
+        Ok(entity)
     }
 
     pub fn parse_content(content: &str) -> Result<VhdlEntity, Box<dyn std::error::Error>> {
@@ -134,10 +184,15 @@ impl VhdlParser {
         // Extract ports
         let ports = Self::extract_ports(&cleaned)?;
 
+        // Extract record types declared alongside the entity (e.g. in the
+        // same file as a locally-visible package)
+        let record_types = Self::extract_record_types(&cleaned);
+
         Ok(VhdlEntity {
             name: entity_name,
             generics,
             ports,
+            record_types,
         })
     }
 
@@ -248,6 +303,48 @@ impl VhdlParser {
         Ok(ports)
     }
 
+    fn extract_record_types(content: &str) -> Vec<VhdlRecordType> {
+        let mut record_types = Vec::new();
+
+        let record_re = match Regex::new(r"type\s+(\w+)\s+is\s+record(.*?)end\s+record\s*;") {
+            Ok(re) => re,
+            Err(_) => return record_types,
+        };
+        let field_re = match Regex::new(r"(\w+)\s*:\s*(\w+(?:_\w+)*)\s*(\([^)]*\))?") {
+            Ok(re) => re,
+            Err(_) => return record_types,
+        };
+
+        for caps in record_re.captures_iter(content) {
+            let name = caps[1].to_string();
+            let body = &caps[2];
+
+            let fields = field_re
+                .captures_iter(body)
+                .map(|f| VhdlRecordField {
+                    name: f[1].to_string(),
+                    field_type: f[2].to_string(),
+                    range: f.get(3).map(|m| m.as_str().to_string()),
+                })
+                .collect();
+
+            record_types.push(VhdlRecordType { name, fields });
+        }
+
+        record_types
+    }
+
+    /// Looks up `signal_type` among the record types known to the design
+    /// (built-in types like `std_logic_vector` are never records).
+    pub fn find_record_type<'a>(
+        signal_type: &str,
+        record_types: &'a [VhdlRecordType],
+    ) -> Option<&'a VhdlRecordType> {
+        record_types
+            .iter()
+            .find(|rt| rt.name.eq_ignore_ascii_case(signal_type))
+    }
+
     fn split_port_declarations_improved(content: &str) -> Vec<String> {
         let mut declarations = Vec::new();
         let mut current = String::new();
@@ -288,17 +385,21 @@ impl VhdlParser {
 
         eprintln!("Parsing cleaned declaration: '{}'", cleaned);
 
-        // More flexible regex that handles ranges better
+        // More flexible regex that handles ranges better. The range itself
+        // is *not* captured here - `[^)]*` would stop at the first `)` and
+        // truncate nested-paren ranges like
+        // `(integer(ceil(log2(real(DEPTH))))-1 downto 0)`, so it's picked
+        // up separately below with paren-depth counting.
         let port_re =
-            Regex::new(r"(?i)(\w+)\s*:\s*(in|out|inout)\s+(\w+(?:_\w+)*)(?:\s*(\([^)]*\)))?")
-                .ok()?;
+            Regex::new(r"(?i)(\w+)\s*:\s*(in|out|inout)\s+(\w+(?:_\w+)*)").ok()?;
 
         if let Some(caps) = port_re.captures(&cleaned) {
-            let range = caps.get(4).map(|m| {
-                let range_str = m.as_str();
-                eprintln!("Captured range: '{}'", range_str);
-                range_str.to_string()
-            });
+            let range = Self::extract_balanced_range(&cleaned[caps.get(0).unwrap().end()..]).map(
+                |range_str| {
+                    eprintln!("Captured range: '{}'", range_str);
+                    range_str
+                },
+            );
 
             Some(VhdlPort {
                 name: caps[1].to_lowercase(),
@@ -311,6 +412,293 @@ impl VhdlParser {
             None
         }
     }
+
+    /// Captures a parenthesized range beginning (after optional leading
+    /// whitespace) at the start of `s`, counting paren depth instead of
+    /// stopping at the first `)` so nested-paren ranges parse whole - the
+    /// same approach `split_port_declarations_improved` uses to find
+    /// statement boundaries without splitting inside a range.
+    fn extract_balanced_range(s: &str) -> Option<String> {
+        let s = s.trim_start();
+        if !s.starts_with('(') {
+            return None;
+        }
+
+        let mut depth = 0;
+        for (i, ch) in s.char_indices() {
+            match ch {
+                '(' => depth += 1,
+                ')' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Some(s[..=i].to_string());
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        None
+    }
+}
+
+/// A value produced while evaluating a range expression. VHDL-2008 keeps
+/// `integer` and `real` distinct types, so we do the same: `log2` and any
+/// division that isn't an exact integer division yield `Real`, while
+/// `integer(...)`, `ceil(...)` and `floor(...)` collapse back to `Int`.
+#[derive(Debug, Clone, Copy)]
+enum ExprValue {
+    Int(i64),
+    Real(f64),
+}
+
+impl ExprValue {
+    fn as_f64(self) -> f64 {
+        match self {
+            ExprValue::Int(i) => i as f64,
+            ExprValue::Real(r) => r,
+        }
+    }
+
+    fn from_f64(value: f64) -> Self {
+        if value.fract() == 0.0 {
+            ExprValue::Int(value as i64)
+        } else {
+            ExprValue::Real(value)
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum ExprToken {
+    Number(f64),
+    Ident(String),
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    LParen,
+    RParen,
+}
+
+fn tokenize_range_expr(expr: &str) -> Result<Vec<ExprToken>, String> {
+    let chars: Vec<char> = expr.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        match chars[i] {
+            c if c.is_whitespace() => i += 1,
+            '+' => {
+                tokens.push(ExprToken::Plus);
+                i += 1;
+            }
+            '-' => {
+                tokens.push(ExprToken::Minus);
+                i += 1;
+            }
+            '*' => {
+                tokens.push(ExprToken::Star);
+                i += 1;
+            }
+            '/' => {
+                tokens.push(ExprToken::Slash);
+                i += 1;
+            }
+            '(' => {
+                tokens.push(ExprToken::LParen);
+                i += 1;
+            }
+            ')' => {
+                tokens.push(ExprToken::RParen);
+                i += 1;
+            }
+            c if c.is_ascii_digit() || c == '.' => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                    i += 1;
+                }
+                let text: String = chars[start..i].iter().collect();
+                let value = text
+                    .parse::<f64>()
+                    .map_err(|_| format!("invalid numeric literal '{}'", text))?;
+                tokens.push(ExprToken::Number(value));
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                    i += 1;
+                }
+                tokens.push(ExprToken::Ident(chars[start..i].iter().collect()));
+            }
+            other => return Err(format!("unexpected character '{}' in range expression", other)),
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Recursive-descent parser/evaluator for the subset of VHDL-2008 arithmetic
+/// used in generic-dependent port ranges: `+ - * /`, parentheses, and the
+/// `log2`/`ceil`/`floor`/`real`/`integer` functions.
+struct RangeExprParser<'a> {
+    tokens: Vec<ExprToken>,
+    pos: usize,
+    generics: &'a HashMap<String, f64>,
+}
+
+impl<'a> RangeExprParser<'a> {
+    fn new(tokens: Vec<ExprToken>, generics: &'a HashMap<String, f64>) -> Self {
+        Self {
+            tokens,
+            pos: 0,
+            generics,
+        }
+    }
+
+    fn peek(&self) -> Option<&ExprToken> {
+        self.tokens.get(self.pos)
+    }
+
+    fn advance(&mut self) -> Option<ExprToken> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn parse(mut self) -> Result<ExprValue, String> {
+        let value = self.parse_additive()?;
+        if self.pos != self.tokens.len() {
+            return Err("trailing characters in range expression".to_string());
+        }
+        Ok(value)
+    }
+
+    fn parse_additive(&mut self) -> Result<ExprValue, String> {
+        let mut value = self.parse_multiplicative()?;
+        loop {
+            match self.peek() {
+                Some(ExprToken::Plus) => {
+                    self.advance();
+                    let rhs = self.parse_multiplicative()?;
+                    value = Self::arith(value, rhs, |a, b| a + b, |a, b| a + b);
+                }
+                Some(ExprToken::Minus) => {
+                    self.advance();
+                    let rhs = self.parse_multiplicative()?;
+                    value = Self::arith(value, rhs, |a, b| a - b, |a, b| a - b);
+                }
+                _ => break,
+            }
+        }
+        Ok(value)
+    }
+
+    fn parse_multiplicative(&mut self) -> Result<ExprValue, String> {
+        let mut value = self.parse_unary()?;
+        loop {
+            match self.peek() {
+                Some(ExprToken::Star) => {
+                    self.advance();
+                    let rhs = self.parse_unary()?;
+                    value = Self::arith(value, rhs, |a, b| a * b, |a, b| a * b);
+                }
+                Some(ExprToken::Slash) => {
+                    self.advance();
+                    let rhs = self.parse_unary()?;
+                    if rhs.as_f64() == 0.0 {
+                        return Err("division by zero in range expression".to_string());
+                    }
+                    value = match (value, rhs) {
+                        (ExprValue::Int(a), ExprValue::Int(b)) if a % b == 0 => {
+                            ExprValue::Int(a / b)
+                        }
+                        _ => ExprValue::Real(value.as_f64() / rhs.as_f64()),
+                    };
+                }
+                _ => break,
+            }
+        }
+        Ok(value)
+    }
+
+    fn arith(
+        a: ExprValue,
+        b: ExprValue,
+        int_op: fn(i64, i64) -> i64,
+        real_op: fn(f64, f64) -> f64,
+    ) -> ExprValue {
+        match (a, b) {
+            (ExprValue::Int(x), ExprValue::Int(y)) => ExprValue::Int(int_op(x, y)),
+            _ => ExprValue::Real(real_op(a.as_f64(), b.as_f64())),
+        }
+    }
+
+    fn parse_unary(&mut self) -> Result<ExprValue, String> {
+        if let Some(ExprToken::Minus) = self.peek() {
+            self.advance();
+            return Ok(match self.parse_unary()? {
+                ExprValue::Int(i) => ExprValue::Int(-i),
+                ExprValue::Real(r) => ExprValue::Real(-r),
+            });
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<ExprValue, String> {
+        match self.advance() {
+            Some(ExprToken::Number(n)) => Ok(ExprValue::from_f64(n)),
+            Some(ExprToken::LParen) => {
+                let value = self.parse_additive()?;
+                match self.advance() {
+                    Some(ExprToken::RParen) => Ok(value),
+                    _ => Err("expected closing parenthesis in range expression".to_string()),
+                }
+            }
+            Some(ExprToken::Ident(name)) => {
+                if let Some(ExprToken::LParen) = self.peek() {
+                    self.advance(); // consume '('
+                    let arg = self.parse_additive()?;
+                    match self.advance() {
+                        Some(ExprToken::RParen) => {}
+                        _ => return Err("expected closing parenthesis in range expression".to_string()),
+                    }
+                    Self::apply_function(&name, arg)
+                } else {
+                    self.generics
+                        .get(&name.to_lowercase())
+                        .copied()
+                        .map(ExprValue::from_f64)
+                        .ok_or_else(|| format!("generic '{}' has no numeric value", name))
+                }
+            }
+            other => Err(format!("unexpected token in range expression: {:?}", other)),
+        }
+    }
+
+    fn apply_function(name: &str, arg: ExprValue) -> Result<ExprValue, String> {
+        match name.to_lowercase().as_str() {
+            "real" => Ok(ExprValue::Real(arg.as_f64())),
+            "integer" => Ok(ExprValue::Int(arg.as_f64().round() as i64)),
+            "ceil" => Ok(ExprValue::Int(arg.as_f64().ceil() as i64)),
+            "floor" => Ok(ExprValue::Int(arg.as_f64().floor() as i64)),
+            "log2" => Ok(ExprValue::Real(arg.as_f64().log2())),
+            other => Err(format!("unsupported function '{}' in range expression", other)),
+        }
+    }
+}
+
+/// Evaluates one side of a `downto`/`to` range (e.g.
+/// `integer(ceil(log2(real(DEPTH))))-1`) to the literal bound VHDL requires.
+fn eval_range_expr(expr: &str, generics: &HashMap<String, f64>) -> Result<i64, String> {
+    let tokens = tokenize_range_expr(expr)?;
+    match RangeExprParser::new(tokens, generics).parse()? {
+        ExprValue::Int(i) => Ok(i),
+        ExprValue::Real(r) => Err(format!(
+            "range bound '{}' evaluated to a non-integer value ({}); wrap it in integer(...)",
+            expr, r
+        )),
+    }
 }
 
 pub struct TestbenchGenerator;
@@ -319,30 +707,81 @@ impl TestbenchGenerator {
     pub fn generate_testbench_data(
         entity: &VhdlEntity,
         config: Option<&TestbenchConfig>,
-    ) -> TestbenchData {
+        generate_package: bool,
+    ) -> Result<TestbenchData, Box<dyn std::error::Error>> {
         let component_name = &entity.name;
-        let ports = Self::generate_ports_string(&entity.ports, &entity.generics, config);
-        let internal_signals =
-            Self::generate_internal_signals(&entity.ports, &entity.generics, config);
-        let port_connections = Self::generate_port_connections(&entity.ports);
+        let reg_style = config.and_then(|c| c.reg_style.as_deref()).unwrap_or("flat");
+        let ports = Self::generate_ports_string(&entity.ports, &entity.generics, config)?;
+        let internal_signals = Self::generate_internal_signals(
+            &entity.ports,
+            &entity.generics,
+            &entity.record_types,
+            config,
+        )?;
+        let port_connections =
+            Self::generate_port_connections(&entity.ports, &entity.record_types, reg_style);
         let clk_gen = Self::generate_clock_generation(config);
         let stim_proc = Self::generate_stimulus_process(&entity.ports, config);
+        let stim_proc_vars = Self::generate_stim_proc_vars(config);
+        let configurations = Self::generate_configurations(entity, config);
+        // Record-typed ports/signals reference a type declared in the
+        // companion `<entity>_pkg.vhd` package (see `generate_package`); the
+        // type is undefined in the testbench without this `use` clause.
+        // `generate_package` also forces it for plain (non-record) entities,
+        // since `--generate-package`'s whole point is a component the DUT
+        // and testbench both draw from, not just records.
+        let package_use = if entity.record_types.is_empty() && !generate_package {
+            String::new()
+        } else {
+            format!("use work.{}_pkg.all;", entity.name)
+        };
 
-        TestbenchData {
+        Ok(TestbenchData {
             component_name: component_name.clone(),
             ports,
             internal_signals,
             port_connections,
             clk_gen,
             stim_proc,
-        }
+            stim_proc_vars,
+            configurations,
+            package_use,
+        })
+    }
+
+    /// Emits one top-level `configuration` per DUT architecture so a single
+    /// testbench can bind `uut` to "rtl", "behavioral", etc. without
+    /// hand-editing the instantiation (VUnit's top-level-configuration idiom).
+    fn generate_configurations(entity: &VhdlEntity, config: Option<&TestbenchConfig>) -> String {
+        let architectures = match config.and_then(|c| c.architectures.as_ref()) {
+            Some(architectures) if !architectures.is_empty() => architectures,
+            _ => return String::new(),
+        };
+
+        let tb_entity = format!("{}_tb", entity.name);
+
+        let blocks: Vec<String> = architectures
+            .iter()
+            .map(|arch| {
+                let config_name = format!("{}_{}", tb_entity, arch);
+                format!(
+                    "configuration {config_name} of {tb_entity} is\n    for testbench\n        for uut : {component}\n            use entity work.{component}({arch});\n        end for;\n    end for;\nend configuration {config_name};",
+                    config_name = config_name,
+                    tb_entity = tb_entity,
+                    component = entity.name,
+                    arch = arch,
+                )
+            })
+            .collect();
+
+        format!("\n{}\n", blocks.join("\n\n"))
     }
 
     fn generate_ports_string(
         ports: &[VhdlPort],
         generics: &[VhdlGeneric],
         config: Option<&TestbenchConfig>,
-    ) -> String {
+    ) -> Result<String, Box<dyn std::error::Error>> {
         let mut result = String::new();
 
         for (i, port) in ports.iter().enumerate() {
@@ -355,7 +794,7 @@ impl TestbenchGenerator {
 
             if let Some(range) = &port.range {
                 // Resolve generic parameters in ranges
-                let resolved_range = Self::resolve_generic_range(range, generics, config);
+                let resolved_range = Self::resolve_generic_range(range, generics, config)?;
                 result.push_str(&resolved_range);
             }
 
@@ -366,51 +805,67 @@ impl TestbenchGenerator {
             }
         }
 
-        result
+        Ok(result)
     }
 
+    /// Resolves a port range such as `(integer(ceil(log2(real(DEPTH))))-1 downto 0)`
+    /// into a literal bound by substituting generic values and evaluating the
+    /// arithmetic/`math_real` expression on each side of `downto`/`to`.
     fn resolve_generic_range(
         range: &str,
         generics: &[VhdlGeneric],
         config: Option<&TestbenchConfig>,
-    ) -> String {
-        let mut resolved = range.to_string();
-        let default_value = "32";
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        let range_re = Regex::new(r"^\(\s*(.+?)\s+(downto|to)\s+(.+?)\s*\)$")?;
+        let caps = match range_re.captures(range.trim()) {
+            Some(caps) => caps,
+            // Not a downto/to range (e.g. already a bare subtype) - leave untouched
+            None => return Ok(range.to_string()),
+        };
 
-        // Replace generic parameters with their values
-        for generic in generics {
-            let generic_name = &generic.name.to_uppercase();
-
-            // Check if config overrides this generic
-            let value = if let Some(config) = config {
-                // First try to get from config
-                if let Some(config_value) = config
-                    .generics
-                    .as_ref()
-                    .and_then(|g| g.get(&generic.name).or_else(|| g.get(generic_name)))
-                {
-                    config_value.as_str()
-                } else {
-                    // Fall back to generic default value
-                    generic.default_value.as_deref().unwrap_or(default_value)
-                }
-            } else {
-                generic.default_value.as_deref().unwrap_or(default_value)
-            };
+        let generic_values = Self::build_generic_values(generics, config);
+
+        let high = eval_range_expr(&caps[1], &generic_values)
+            .map_err(|e| format!("failed to resolve range '{}': {}", range, e))?;
+        let direction = &caps[2];
+        let low = eval_range_expr(&caps[3], &generic_values)
+            .map_err(|e| format!("failed to resolve range '{}': {}", range, e))?;
+
+        Ok(format!("({} {} {})", high, direction, low))
+    }
+
+    /// Builds the generic-name -> numeric-value environment used to evaluate
+    /// range expressions, preferring a config override over the generic's
+    /// declared default. Generics with no numeric value are omitted rather
+    /// than defaulted, so referencing one produces a clear error.
+    fn build_generic_values(
+        generics: &[VhdlGeneric],
+        config: Option<&TestbenchConfig>,
+    ) -> HashMap<String, f64> {
+        let mut values = HashMap::new();
 
-            // Replace both uppercase and original case
-            resolved = resolved.replace(generic_name, value);
-            resolved = resolved.replace(&generic.name, value);
+        for generic in generics {
+            let generic_name_upper = generic.name.to_uppercase();
+            let raw_value = config
+                .and_then(|c| c.generics.as_ref())
+                .and_then(|g| g.get(&generic.name).or_else(|| g.get(&generic_name_upper)))
+                .map(|s| s.as_str())
+                .or(generic.default_value.as_deref());
+
+            if let Some(parsed) = raw_value.and_then(|v| v.parse::<f64>().ok()) {
+                values.insert(generic.name.to_lowercase(), parsed);
+            }
         }
 
-        resolved
+        values
     }
 
     fn generate_internal_signals(
         ports: &[VhdlPort],
         generics: &[VhdlGeneric],
+        record_types: &[VhdlRecordType],
         config: Option<&TestbenchConfig>,
-    ) -> String {
+    ) -> Result<String, Box<dyn std::error::Error>> {
         let mut signals = Vec::new();
 
         for port in ports {
@@ -423,16 +878,19 @@ impl TestbenchGenerator {
 
             if let Some(range) = &port.range {
                 // Resolve generic parameters in ranges
-                let resolved_range = Self::resolve_generic_range(range, generics, config);
+                let resolved_range = Self::resolve_generic_range(range, generics, config)?;
                 signal_decl.push_str(&resolved_range);
             }
 
-            // Add default values for testbench signals
-            match port.signal_type.to_uppercase().as_str() {
-                "STD_LOGIC" => signal_decl.push_str(" := '0'"),
-                "STD_LOGIC_VECTOR" => signal_decl.push_str(" := (others => '0')"),
-                "INTEGER" => signal_decl.push_str(" := 0"),
-                _ => signal_decl.push_str(" := '0'"),
+            // Record-typed signals carry their own field defaults; only
+            // scalar/vector testbench signals need an explicit init value.
+            if VhdlParser::find_record_type(&port.signal_type, record_types).is_none() {
+                match port.signal_type.to_uppercase().as_str() {
+                    "STD_LOGIC" => signal_decl.push_str(" := '0'"),
+                    "STD_LOGIC_VECTOR" => signal_decl.push_str(" := (others => '0')"),
+                    "INTEGER" => signal_decl.push_str(" := 0"),
+                    _ => signal_decl.push_str(" := '0'"),
+                }
             }
 
             signal_decl.push(';');
@@ -444,33 +902,135 @@ impl TestbenchGenerator {
             signals.push("signal tb_clk : STD_LOGIC := '0';".to_string());
         }
 
-        signals.join("\n")
+        // Extra named clocks (beyond the default tb_clk) each get their own
+        // STD_LOGIC signal, unless an entity port of the same name already
+        // covers it.
+        if let Some(clocks) = config.and_then(|c| c.clocks.as_ref()) {
+            let mut names: Vec<&String> = clocks.keys().collect();
+            names.sort();
+            for name in names {
+                if !ports.iter().any(|p| p.name.eq_ignore_ascii_case(name)) {
+                    signals.push(format!("signal tb_{} : STD_LOGIC := '0';", name));
+                }
+            }
+        }
+
+        // Self-terminating clock generators toggle only while this flag
+        // holds; the stimulus process clears it once testing is done.
+        signals.push("signal running : boolean := true;".to_string());
+
+        Ok(signals.join("\n"))
+    }
+
+    /// Declarative-part variables for `stim_proc`. The scoreboard's mismatch
+    /// count is a process variable rather than a signal: it is incremented
+    /// and read back within the same process, and a signal's deferred
+    /// (delta-cycle) update would both collapse multiple mismatches in one
+    /// vector into a single +1 and let the final pass/fail check run before
+    /// the last vector's increment ever lands.
+    fn generate_stim_proc_vars(config: Option<&TestbenchConfig>) -> String {
+        if Self::uses_scoreboard(config) {
+            "    variable error_count : integer := 0;".to_string()
+        } else {
+            String::new()
+        }
+    }
+
+    fn uses_scoreboard(config: Option<&TestbenchConfig>) -> bool {
+        config
+            .and_then(|c| c.test_vectors.as_ref())
+            .is_some_and(|vectors| vectors.iter().any(|v| v.expected_outputs.is_some()))
     }
 
-    fn generate_port_connections(ports: &[VhdlPort]) -> String {
+    fn generate_port_connections(
+        ports: &[VhdlPort],
+        record_types: &[VhdlRecordType],
+        reg_style: &str,
+    ) -> String {
         let mut connections = Vec::new();
 
-        for (i, port) in ports.iter().enumerate() {
-            let connection = format!("        {} => tb_{}", port.name, port.name);
-            if i < ports.len() - 1 {
-                connections.push(format!("{},", connection));
-            } else {
-                connections.push(connection);
+        for port in ports {
+            let record_type = (reg_style == "record")
+                .then(|| VhdlParser::find_record_type(&port.signal_type, record_types))
+                .flatten();
+
+            match record_type {
+                Some(record_type) => {
+                    for field in &record_type.fields {
+                        connections.push(format!(
+                            "        {}.{} => tb_{}.{},",
+                            port.name, field.name, port.name, field.name
+                        ));
+                    }
+                }
+                None => {
+                    connections.push(format!("        {} => tb_{},", port.name, port.name));
+                }
             }
         }
 
+        // Drop the trailing comma on the final connection
+        if let Some(stripped) = connections.last().and_then(|last| last.strip_suffix(',')) {
+            let stripped = stripped.to_string();
+            *connections.last_mut().unwrap() = stripped;
+        }
+
         connections.join("\n")
     }
 
     fn generate_clock_generation(config: Option<&TestbenchConfig>) -> String {
+        // The default tb_clk generator is always emitted; a `clocks` table
+        // adds further generators alongside it (see the field doc on
+        // `TestbenchConfig::clocks`) rather than replacing it.
         let period = config.and_then(|c| c.clock_period_ns).unwrap_or(10); // Default 10ns period (100MHz)
+        let mut generators = vec![Self::generate_single_clock_gen("tb_clk", period, None)];
+
+        if let Some(clocks) = config.and_then(|c| c.clocks.as_ref()).filter(|c| !c.is_empty()) {
+            let mut entries: Vec<(&String, &ClockConfig)> = clocks.iter().collect();
+            entries.sort_by_key(|(name, _)| name.as_str());
+            generators.extend(entries.into_iter().map(|(name, clk)| {
+                Self::generate_single_clock_gen(
+                    &format!("tb_{}", name),
+                    clk.period_ns,
+                    clk.phase_offset_ns,
+                )
+            }));
+        }
 
+        generators.join("\n")
+    }
+
+    /// Emits the self-terminating clock idiom for one signal: it keeps
+    /// toggling every half period until `running` goes false, at which point
+    /// the generator holds its last value instead of looping forever.
+    ///
+    /// Without a phase offset this is a single concurrent conditional
+    /// waveform assignment. With one, a waveform won't do: `sig <= '0'
+    /// after phase, not sig after phase + half when running else sig` is
+    /// sensitive to `sig` itself, so every toggle re-evaluates the whole
+    /// two-element waveform and reschedules it from the initial `'0' after
+    /// phase` pulse again, never settling into a periodic clock. A process
+    /// that waits out the phase once and then loops is used instead.
+    fn generate_single_clock_gen(signal: &str, period: u32, phase_offset_ns: Option<u32>) -> String {
         let half_period = period / 2;
 
-        format!(
-            "    tb_clk <= '0';\n    wait for {} ns;\n    tb_clk <= '1';\n    wait for {} ns;",
-            half_period, half_period
-        )
+        match phase_offset_ns {
+            // `wait until not running for {half} ns` wakes the instant
+            // `running` drops, instead of only at the next loop boundary,
+            // so this stops on the same delta cycle as the concurrent
+            // (no-phase) generator rather than toggling one extra time.
+            Some(phase) if phase > 0 => format!(
+                "    {sig}_gen: process\n    begin\n        wait for {phase} ns;\n        while running loop\n            wait until not running for {half} ns;\n            if running then\n                {sig} <= not {sig};\n            end if;\n        end loop;\n        wait;\n    end process;",
+                sig = signal,
+                phase = phase,
+                half = half_period
+            ),
+            _ => format!(
+                "    {sig} <= not {sig} after {half} ns when running else {sig};",
+                sig = signal,
+                half = half_period
+            ),
+        }
     }
 
     fn generate_stimulus_process(ports: &[VhdlPort], config: Option<&TestbenchConfig>) -> String {
@@ -503,21 +1063,33 @@ impl TestbenchGenerator {
 
                     stimulus.push_str(&format!("    wait for {} ns;\n", vector.time_ns));
 
-                    // Check expected outputs if provided
+                    // Check expected outputs if provided, scoring each
+                    // mismatch into the running error_count
                     if let Some(expected) = &vector.expected_outputs {
                         for (signal, expected_value) in expected {
-                            // Determine the signal type for proper formatting
-                            let format_func = if expected_value.starts_with("x\"")
-                                || expected_value.contains("downto")
+                            // Pick the mismatch-report formatter off the
+                            // port's declared type, not the expected-value
+                            // literal's spelling - a vector output's
+                            // `expected` is just as often a plain binary
+                            // string ("00000000") as a hex literal, and
+                            // `std_logic'image` doesn't even typecheck
+                            // against a vector.
+                            let format_func = match ports
+                                .iter()
+                                .find(|p| &p.name == signal)
+                                .map(|p| p.signal_type.to_uppercase())
+                                .as_deref()
                             {
-                                "to_hstring" // For vectors
-                            } else {
-                                "std_logic'image" // For single bits
+                                Some("STD_LOGIC_VECTOR") => "to_hstring",
+                                _ => "std_logic'image",
                             };
 
                             stimulus.push_str(&format!(
-                                "    assert tb_{} = {} report \"Expected {} = {}, got \" & {}(tb_{}) severity error;\n",
-                                signal, expected_value, signal, expected_value, format_func, signal
+                                "    if tb_{signal} /= {expected_value} then\n        error_count := error_count + 1;\n        report \"Mismatch at vector {vector_num}: {signal} expected {expected_value}, got \" & {format_func}(tb_{signal}) severity error;\n    end if;\n",
+                                signal = signal,
+                                expected_value = expected_value,
+                                vector_num = i + 1,
+                                format_func = format_func,
                             ));
                         }
                     }
@@ -533,7 +1105,21 @@ impl TestbenchGenerator {
             stimulus.push_str(&Self::generate_basic_test(ports));
         }
 
-        stimulus.push_str("    -- End of test\n    report \"Test completed\" severity note;\n");
+        // Stop the clock generators now that the last vector has run
+        stimulus.push_str("    running <= false;\n");
+
+        if Self::uses_scoreboard(config) {
+            stimulus.push_str("    -- End of test\n");
+            stimulus.push_str("    if error_count = 0 then\n");
+            stimulus.push_str("        report \"Test completed: all vectors passed\" severity note;\n");
+            stimulus.push_str("    else\n");
+            stimulus.push_str(
+                "        report \"Test completed: \" & integer'image(error_count) & \" mismatch(es)\" severity failure;\n",
+            );
+            stimulus.push_str("    end if;\n");
+        } else {
+            stimulus.push_str("    -- End of test\n    report \"Test completed\" severity note;\n");
+        }
         stimulus
     }
 
@@ -596,6 +1182,9 @@ pub struct TestbenchData {
     pub port_connections: String,
     pub clk_gen: String,
     pub stim_proc: String,
+    pub stim_proc_vars: String,
+    pub configurations: String,
+    pub package_use: String,
 }
 
 impl TestbenchData {
@@ -607,6 +1196,9 @@ impl TestbenchData {
             .replace("{port_connections}", &self.port_connections)
             .replace("{clk_gen}", &self.clk_gen)
             .replace("{stim_proc}", &self.stim_proc)
+            .replace("{stim_proc_vars}", &self.stim_proc_vars)
+            .replace("{configurations}", &self.configurations)
+            .replace("{package_use}", &self.package_use)
     }
 }
 
@@ -631,7 +1223,7 @@ pub fn generate_baseline_config(entity: &VhdlEntity) -> TestbenchConfig {
         match port.direction.as_str() {
             "in" => {
                 let sample_value = match port.signal_type.as_str() {
-                    "std_logic" => "0".to_string(),
+                    "std_logic" => "'0'".to_string(),
                     "std_logic_vector" => {
                         if port.range.is_some() {
                             "\"00000000\"".to_string() // Default 8-bit vector
@@ -645,7 +1237,7 @@ pub fn generate_baseline_config(entity: &VhdlEntity) -> TestbenchConfig {
             }
             "out" => {
                 let expected_value = match port.signal_type.as_str() {
-                    "std_logic" => "0".to_string(),
+                    "std_logic" => "'0'".to_string(),
                     "std_logic_vector" => {
                         if port.range.is_some() {
                             "\"00000000\"".to_string() // Default 8-bit vector
@@ -681,6 +1273,9 @@ pub fn generate_baseline_config(entity: &VhdlEntity) -> TestbenchConfig {
         reset_duration_ns: Some(100),
         test_vectors: Some(vec![sample_test_vector]),
         generics: if generics_map.is_empty() { None } else { Some(generics_map) },
+        reg_style: None,
+        architectures: None,
+        clocks: None,
     }
 }
 
@@ -773,6 +1368,101 @@ pub fn save_vhdl_template<P: AsRef<Path>>(entity_name: &str, path: P) -> Result<
     Ok(())
 }
 
+/// Renders the `component`-declaration package for an entity, e.g.
+/// `use work.<entity>_pkg.all;` in both the DUT and the generated
+/// testbench so port lists stay in sync with a single authoritative
+/// definition instead of an inline copy.
+pub fn generate_package(entity: &VhdlEntity) -> String {
+    let package_name = format!("{}_pkg", entity.name);
+
+    let mut body = String::new();
+
+    if !entity.record_types.is_empty() {
+        body.push_str("    -- Record interface types\n");
+        for record_type in &entity.record_types {
+            body.push_str(&format!("    type {} is record\n", record_type.name));
+            for field in &record_type.fields {
+                body.push_str(&format!(
+                    "        {} : {}{};\n",
+                    field.name,
+                    field.field_type.to_uppercase(),
+                    field.range.as_deref().unwrap_or("")
+                ));
+            }
+            body.push_str("    end record;\n\n");
+        }
+    }
+
+    body.push_str(&format!("    component {}\n", entity.name));
+
+    if !entity.generics.is_empty() {
+        body.push_str("        Generic (\n");
+        let generic_lines: Vec<String> = entity
+            .generics
+            .iter()
+            .map(|generic| {
+                let mut line = format!(
+                    "            {} : {}",
+                    generic.name,
+                    generic.generic_type.to_uppercase()
+                );
+                if let Some(default_value) = &generic.default_value {
+                    line.push_str(&format!(" := {}", default_value));
+                }
+                line
+            })
+            .collect();
+        body.push_str(&generic_lines.join(";\n"));
+        body.push_str("\n        );\n");
+    }
+
+    body.push_str("        Port (\n");
+    let port_lines: Vec<String> = entity
+        .ports
+        .iter()
+        .map(|port| {
+            format!(
+                "            {} : {} {}{}",
+                port.name,
+                port.direction.to_uppercase(),
+                port.signal_type.to_uppercase(),
+                port.range.as_deref().unwrap_or("")
+            )
+        })
+        .collect();
+    body.push_str(&port_lines.join(";\n"));
+    body.push_str("\n        );\n");
+    body.push_str("    end component;\n");
+
+    format!(
+        r#"-- =============================================================================
+-- Package: {package_name}
+-- Component declaration for {entity_name}, generated to keep the DUT and
+-- testbench port lists in sync. `use work.{package_name}.all;` from both.
+-- =============================================================================
+library IEEE;
+use IEEE.STD_LOGIC_1164.ALL;
+use IEEE.NUMERIC_STD.ALL;
+
+package {package_name} is
+
+{body}
+end package {package_name};
+"#,
+        package_name = package_name,
+        entity_name = entity.name,
+        body = body
+    )
+}
+
+pub fn save_package<P: AsRef<Path>>(entity: &VhdlEntity, path: P) -> Result<(), Box<dyn std::error::Error>> {
+    let path = path.as_ref();
+    let package_content = generate_package(entity);
+    fs::write(path, package_content)
+        .map_err(|e| format!("Failed to write package file '{}': {}", path.display(), e))?;
+    Ok(())
+}
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let matches = Command::new("VHDL Testbench Generator")
         .version("1.0")
@@ -828,12 +1518,26 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 .value_name("ENTITY_NAME")
                 .help("Generate a VHDL entity template with the specified name and exit"),
         )
+        .arg(
+            Arg::new("reg_style")
+                .long("reg-style")
+                .value_name("STYLE")
+                .help("Port-map wiring style for record-typed ports: flat (default) or record")
+                .value_parser(["flat", "record"]),
+        )
+        .arg(
+            Arg::new("generate_package")
+                .long("generate-package")
+                .help("Also emit a <entity>_pkg.vhd package with the component declaration")
+                .action(clap::ArgAction::SetTrue),
+        )
         .get_matches();
 
     let input_file = matches.get_one::<String>("input");
     let verbose = matches.get_flag("verbose");
     let generate_config = matches.get_flag("generate_config");
     let generate_template = matches.get_one::<String>("generate_template");
+    let generate_package = matches.get_flag("generate_package");
 
     // If generate-template flag is set, generate VHDL template and exit
     if let Some(entity_name) = generate_template {
@@ -880,7 +1584,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     }
 
     // Load optional config
-    let config = if let Some(config_file) = matches.get_one::<String>("config") {
+    let mut config = if let Some(config_file) = matches.get_one::<String>("config") {
         if verbose {
             println!("Loading config from: {}", config_file);
         }
@@ -898,8 +1602,25 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     };
 
+    // --reg-style overrides whatever the config file says (or stands alone
+    // if no config file was loaded)
+    if let Some(reg_style) = matches.get_one::<String>("reg_style") {
+        config
+            .get_or_insert(TestbenchConfig {
+                clock_period_ns: None,
+                reset_duration_ns: None,
+                test_vectors: None,
+                generics: None,
+                reg_style: None,
+                architectures: None,
+                clocks: None,
+            })
+            .reg_style = Some(reg_style.clone());
+    }
+
     // Generate testbench data
-    let testbench_data = TestbenchGenerator::generate_testbench_data(&entity, config.as_ref());
+    let testbench_data =
+        TestbenchGenerator::generate_testbench_data(&entity, config.as_ref(), generate_package)?;
 
     // Load template
     let template = if let Some(template_file) = matches.get_one::<String>("template") {
@@ -931,6 +1652,14 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     println!("Testbench generated successfully: {}", output_file);
 
+    // If requested, also emit the companion package with the component
+    // declaration so the DUT and testbench can both `use work.<pkg>.all;`
+    if generate_package {
+        let package_filename = format!("{}_pkg.vhd", entity.name);
+        save_package(&entity, &package_filename)?;
+        println!("Generated package file: {}", package_filename);
+    }
+
     if verbose {
         println!("Generated testbench contains:");
         println!("  Component: {}", testbench_data.component_name);
@@ -1028,6 +1757,7 @@ mod tests {
                     range: Some("(DATA_WIDTH-1 downto 0)".to_string()),
                 },
             ],
+            record_types: Vec::new(),
         };
 
         let config = TestbenchConfig {
@@ -1038,12 +1768,414 @@ mod tests {
                 "32".to_string(),
             )])),
             test_vectors: None,
+            reg_style: None,
+            architectures: None,
+            clocks: None,
         };
 
-        let testbench_data = TestbenchGenerator::generate_testbench_data(&entity, Some(&config));
+        let testbench_data = TestbenchGenerator::generate_testbench_data(&entity, Some(&config), false).unwrap();
 
         // Check that ranges are resolved
         assert!(testbench_data.ports.contains("(31 downto 0)"));
         assert!(testbench_data.internal_signals.contains("(31 downto 0)"));
     }
+
+    #[test]
+    fn test_record_port_wiring() {
+        let vhdl_content = r#"
+        entity wb_core is
+          port (
+            clk : in STD_LOGIC;
+            wb_i : in wbm_in_type;
+            wb_o : out wbm_out_type
+          );
+        end entity wb_core;
+
+        type wbm_in_type is record
+          ack : std_logic;
+          dat : std_logic_vector(31 downto 0);
+        end record;
+
+        type wbm_out_type is record
+          cyc : std_logic;
+          stb : std_logic;
+        end record;
+        "#;
+
+        let entity = VhdlParser::parse_content(vhdl_content).unwrap();
+        assert_eq!(entity.record_types.len(), 2);
+
+        let config = TestbenchConfig {
+            clock_period_ns: None,
+            reset_duration_ns: None,
+            generics: None,
+            test_vectors: None,
+            reg_style: Some("record".to_string()),
+            architectures: None,
+            clocks: None,
+        };
+
+        let testbench_data = TestbenchGenerator::generate_testbench_data(&entity, Some(&config), false).unwrap();
+
+        assert!(testbench_data
+            .port_connections
+            .contains("wb_i.ack => tb_wb_i.ack"));
+        assert!(testbench_data
+            .port_connections
+            .contains("wb_o.cyc => tb_wb_o.cyc"));
+        assert!(testbench_data
+            .package_use
+            .contains("use work.wb_core_pkg.all;"));
+    }
+
+    #[test]
+    fn test_generate_package() {
+        let entity = VhdlEntity {
+            name: "stack".to_string(),
+            generics: vec![VhdlGeneric {
+                name: "DATA_WIDTH".to_string(),
+                generic_type: "INTEGER".to_string(),
+                default_value: Some("32".to_string()),
+            }],
+            ports: vec![
+                VhdlPort {
+                    name: "clk".to_string(),
+                    direction: "in".to_string(),
+                    signal_type: "STD_LOGIC".to_string(),
+                    range: None,
+                },
+                VhdlPort {
+                    name: "data_out".to_string(),
+                    direction: "out".to_string(),
+                    signal_type: "STD_LOGIC_VECTOR".to_string(),
+                    range: Some("(DATA_WIDTH-1 downto 0)".to_string()),
+                },
+            ],
+            record_types: Vec::new(),
+        };
+
+        let package = generate_package(&entity);
+
+        assert!(package.contains("package stack_pkg is"));
+        assert!(package.contains("component stack"));
+        assert!(package.contains("DATA_WIDTH : INTEGER := 32"));
+        assert!(package.contains("data_out : OUT STD_LOGIC_VECTOR(DATA_WIDTH-1 downto 0)"));
+        assert!(package.contains("end package stack_pkg;"));
+
+        // --generate-package must wire the package into the testbench even
+        // for a plain entity with no record ports, otherwise its whole
+        // point (DUT and testbench sharing one port-list definition) is
+        // never exercised.
+        let testbench_data =
+            TestbenchGenerator::generate_testbench_data(&entity, None, true).unwrap();
+        assert!(testbench_data
+            .package_use
+            .contains("use work.stack_pkg.all;"));
+    }
+
+    #[test]
+    fn test_self_checking_scoreboard() {
+        let entity = VhdlEntity {
+            name: "stack".to_string(),
+            generics: vec![],
+            ports: vec![VhdlPort {
+                name: "data_out".to_string(),
+                direction: "out".to_string(),
+                signal_type: "STD_LOGIC".to_string(),
+                range: None,
+            }],
+            record_types: Vec::new(),
+        };
+
+        let mut expected = HashMap::new();
+        expected.insert("data_out".to_string(), "'1'".to_string());
+
+        let config = TestbenchConfig {
+            clock_period_ns: Some(10),
+            reset_duration_ns: Some(100),
+            generics: None,
+            test_vectors: Some(vec![TestVector {
+                time_ns: 20,
+                inputs: HashMap::new(),
+                expected_outputs: Some(expected),
+                description: None,
+            }]),
+            reg_style: None,
+            architectures: None,
+            clocks: None,
+        };
+
+        let testbench_data = TestbenchGenerator::generate_testbench_data(&entity, Some(&config), false).unwrap();
+
+        assert!(testbench_data
+            .stim_proc_vars
+            .contains("variable error_count : integer := 0;"));
+        assert!(testbench_data
+            .stim_proc
+            .contains("error_count := error_count + 1;"));
+        assert!(testbench_data
+            .stim_proc
+            .contains("Mismatch at vector 1: data_out"));
+        assert!(testbench_data.stim_proc.contains("severity failure;"));
+    }
+
+    #[test]
+    fn test_range_expr_math_real_idiom() {
+        let entity = VhdlEntity {
+            name: "fifo".to_string(),
+            generics: vec![VhdlGeneric {
+                name: "depth".to_string(),
+                generic_type: "integer".to_string(),
+                default_value: Some("16".to_string()),
+            }],
+            ports: vec![VhdlPort {
+                name: "sel".to_string(),
+                direction: "out".to_string(),
+                signal_type: "std_logic_vector".to_string(),
+                range: Some("(integer(ceil(log2(real(depth))))-1 downto 0)".to_string()),
+            }],
+            record_types: Vec::new(),
+        };
+
+        let testbench_data = TestbenchGenerator::generate_testbench_data(&entity, None, false).unwrap();
+
+        // ceil(log2(16)) = 4, so the selector is (3 downto 0)
+        assert!(testbench_data.ports.contains("(3 downto 0)"));
+    }
+
+    #[test]
+    fn test_parse_nested_paren_range() {
+        let vhdl_content = r#"
+        entity selwid is
+          GENERIC (DEPTH : INTEGER := 16);
+          port (
+            sel : out STD_LOGIC_VECTOR(integer(ceil(log2(real(DEPTH))))-1 downto 0)
+          );
+        end entity selwid;
+        "#;
+
+        let entity = VhdlParser::parse_content(vhdl_content).unwrap();
+        let sel_port = entity.ports.iter().find(|p| p.name == "sel").unwrap();
+
+        // A non-greedy `[^)]*` range capture would truncate at the first
+        // `)` and lose the `downto 0` half of the range entirely.
+        assert_eq!(
+            sel_port.range,
+            Some("(integer(ceil(log2(real(depth))))-1 downto 0)".to_string())
+        );
+    }
+
+    #[test]
+    fn test_range_expr_errors_are_reported_not_panics() {
+        let generics = HashMap::new();
+        let err = eval_range_expr("missing_generic - 1", &generics).unwrap_err();
+        assert!(err.contains("no numeric value"));
+
+        let err = eval_range_expr("4 / 0", &generics).unwrap_err();
+        assert!(err.contains("division by zero"));
+    }
+
+    #[test]
+    fn test_multi_architecture_configurations() {
+        let entity = VhdlEntity {
+            name: "fifo".to_string(),
+            generics: vec![],
+            ports: vec![],
+            record_types: Vec::new(),
+        };
+
+        let config = TestbenchConfig {
+            clock_period_ns: None,
+            reset_duration_ns: None,
+            generics: None,
+            test_vectors: None,
+            reg_style: None,
+            architectures: Some(vec!["rtl".to_string(), "behavioral".to_string()]),
+            clocks: None,
+        };
+
+        let testbench_data = TestbenchGenerator::generate_testbench_data(&entity, Some(&config), false).unwrap();
+
+        assert!(testbench_data
+            .configurations
+            .contains("configuration fifo_tb_rtl of fifo_tb is"));
+        assert!(testbench_data
+            .configurations
+            .contains("use entity work.fifo(rtl);"));
+        assert!(testbench_data
+            .configurations
+            .contains("configuration fifo_tb_behavioral of fifo_tb is"));
+        assert!(testbench_data
+            .configurations
+            .contains("end configuration fifo_tb_behavioral;"));
+    }
+
+    #[test]
+    fn test_self_terminating_clock_generation() {
+        let entity = VhdlEntity {
+            name: "fifo".to_string(),
+            generics: vec![],
+            ports: vec![],
+            record_types: Vec::new(),
+        };
+
+        let config = TestbenchConfig {
+            clock_period_ns: Some(20),
+            reset_duration_ns: None,
+            generics: None,
+            test_vectors: None,
+            reg_style: None,
+            architectures: None,
+            clocks: None,
+        };
+
+        let testbench_data = TestbenchGenerator::generate_testbench_data(&entity, Some(&config), false).unwrap();
+
+        assert!(testbench_data
+            .internal_signals
+            .contains("signal running : boolean := true;"));
+        assert!(testbench_data
+            .clk_gen
+            .contains("tb_clk <= not tb_clk after 10 ns when running else tb_clk;"));
+        assert!(testbench_data.stim_proc.contains("running <= false;"));
+    }
+
+    #[test]
+    fn test_multiple_named_clocks_with_phase_offset() {
+        let entity = VhdlEntity {
+            name: "fifo".to_string(),
+            generics: vec![],
+            ports: vec![],
+            record_types: Vec::new(),
+        };
+
+        let mut clocks = HashMap::new();
+        clocks.insert(
+            "core_clk".to_string(),
+            ClockConfig {
+                period_ns: 10,
+                phase_offset_ns: None,
+            },
+        );
+        clocks.insert(
+            "bus_clk".to_string(),
+            ClockConfig {
+                period_ns: 8,
+                phase_offset_ns: Some(3),
+            },
+        );
+
+        let config = TestbenchConfig {
+            clock_period_ns: None,
+            reset_duration_ns: None,
+            generics: None,
+            test_vectors: None,
+            reg_style: None,
+            architectures: None,
+            clocks: Some(clocks),
+        };
+
+        let testbench_data = TestbenchGenerator::generate_testbench_data(&entity, Some(&config), false).unwrap();
+
+        assert!(testbench_data
+            .internal_signals
+            .contains("signal tb_core_clk : STD_LOGIC := '0';"));
+        assert!(testbench_data
+            .internal_signals
+            .contains("signal tb_bus_clk : STD_LOGIC := '0';"));
+        assert!(testbench_data
+            .clk_gen
+            .contains("tb_bus_clk_gen: process"));
+        assert!(testbench_data.clk_gen.contains("wait for 3 ns;"));
+        assert!(testbench_data
+            .clk_gen
+            .contains("wait until not running for 4 ns;"));
+        assert!(testbench_data
+            .clk_gen
+            .contains("tb_bus_clk <= not tb_bus_clk;"));
+        assert!(testbench_data
+            .clk_gen
+            .contains("tb_core_clk <= not tb_core_clk after 5 ns when running else tb_core_clk;"));
+        // The default tb_clk generator is still emitted alongside the named
+        // clocks, not replaced by them.
+        assert!(testbench_data
+            .clk_gen
+            .contains("tb_clk <= not tb_clk after 5 ns when running else tb_clk;"));
+    }
+
+    #[test]
+    fn test_baseline_config_emits_compilable_literals() {
+        let entity = VhdlEntity {
+            name: "stack".to_string(),
+            generics: vec![],
+            ports: vec![
+                VhdlPort {
+                    name: "push".to_string(),
+                    direction: "in".to_string(),
+                    signal_type: "std_logic".to_string(),
+                    range: None,
+                },
+                VhdlPort {
+                    name: "data_out".to_string(),
+                    direction: "out".to_string(),
+                    signal_type: "std_logic".to_string(),
+                    range: None,
+                },
+            ],
+            record_types: Vec::new(),
+        };
+
+        let config = generate_baseline_config(&entity);
+        let vector = &config.test_vectors.unwrap()[0];
+
+        assert_eq!(vector.inputs.get("push"), Some(&"'0'".to_string()));
+        assert_eq!(
+            vector.expected_outputs.as_ref().unwrap().get("data_out"),
+            Some(&"'0'".to_string())
+        );
+    }
+
+    #[test]
+    fn test_scoreboard_formats_vector_mismatch_with_to_hstring() {
+        let entity = VhdlEntity {
+            name: "fifo".to_string(),
+            generics: vec![],
+            ports: vec![VhdlPort {
+                name: "data_out".to_string(),
+                direction: "out".to_string(),
+                signal_type: "std_logic_vector".to_string(),
+                range: Some("(7 downto 0)".to_string()),
+            }],
+            record_types: Vec::new(),
+        };
+
+        let mut expected = HashMap::new();
+        expected.insert("data_out".to_string(), "\"00000000\"".to_string());
+
+        let config = TestbenchConfig {
+            clock_period_ns: Some(10),
+            reset_duration_ns: Some(100),
+            generics: None,
+            test_vectors: Some(vec![TestVector {
+                time_ns: 20,
+                inputs: HashMap::new(),
+                expected_outputs: Some(expected),
+                description: None,
+            }]),
+            reg_style: None,
+            architectures: None,
+            clocks: None,
+        };
+
+        let testbench_data = TestbenchGenerator::generate_testbench_data(&entity, Some(&config), false).unwrap();
+
+        // A plain binary expected literal must still pick to_hstring since
+        // it's keyed off the port's declared type, not the literal's shape.
+        assert!(testbench_data
+            .stim_proc
+            .contains("to_hstring(tb_data_out)"));
+        assert!(!testbench_data
+            .stim_proc
+            .contains("std_logic'image(tb_data_out)"));
+    }
 }